@@ -0,0 +1,131 @@
+use serde::Serialize;
+use sqlx::PgPool;
+use std::time::Duration;
+
+const RANKING_WINDOW_SECS: i64 = 7 * 24 * 60 * 60;
+const SNAPSHOT_SIZE: i64 = 20;
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct BestSellingSnapshot {
+    pub fetched_at: i64,
+    pub category: String,
+    /// Ranked product keys, most-compared/most-discounted first.
+    pub ranked_products: serde_json::Value,
+}
+
+/// Stores periodic best-selling rankings per `product_category`, computed
+/// from observed `price_history` rather than on every request.
+pub struct BestSellingStore {
+    pool: PgPool,
+}
+
+impl BestSellingStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn ensure_schema(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS best_selling (
+                id BIGSERIAL PRIMARY KEY,
+                fetched_at BIGINT NOT NULL,
+                category TEXT NOT NULL,
+                ranked_products JSONB NOT NULL
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS best_selling_category_idx \
+             ON best_selling (category, fetched_at DESC)",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Returns the most recent snapshot for `category`, if one has run yet.
+    pub async fn latest(&self, category: &str) -> Result<Option<BestSellingSnapshot>, sqlx::Error> {
+        sqlx::query_as::<_, BestSellingSnapshot>(
+            "SELECT fetched_at, category, ranked_products FROM best_selling \
+             WHERE category = $1 ORDER BY fetched_at DESC LIMIT 1",
+        )
+        .bind(category)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn categories_seen_since(&self, since: i64) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar(
+            "SELECT DISTINCT product_category FROM price_history \
+             WHERE product_category IS NOT NULL AND fetched_at >= $1",
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn rank_category(&self, category: &str, since: i64) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar(
+            "SELECT product_key FROM price_history \
+             WHERE product_category = $1 AND fetched_at >= $2 \
+             GROUP BY product_key \
+             ORDER BY COUNT(*) DESC, (MAX(price) - MIN(price)) DESC \
+             LIMIT $3",
+        )
+        .bind(category)
+        .bind(since)
+        .bind(SNAPSHOT_SIZE)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Aggregates the last `RANKING_WINDOW_SECS` of comparisons into one
+    /// ranked snapshot per category that was actually compared in that
+    /// window.
+    pub async fn refresh(&self, now: i64) -> Result<(), sqlx::Error> {
+        let since = now - RANKING_WINDOW_SECS;
+        for category in self.categories_seen_since(since).await? {
+            let ranked = self.rank_category(&category, since).await?;
+
+            sqlx::query(
+                "INSERT INTO best_selling (fetched_at, category, ranked_products) \
+                 VALUES ($1, $2, $3)",
+            )
+            .bind(now)
+            .bind(&category)
+            .bind(serde_json::json!(ranked))
+            .execute(&self.pool)
+            .await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs `BestSellingStore::refresh` on a fixed interval for the lifetime of
+/// the process. Intended to be spawned once alongside the comparison
+/// service.
+pub async fn run_snapshot_task(pool: PgPool, interval: Duration) {
+    let store = BestSellingStore::new(pool);
+    if let Err(err) = store.ensure_schema().await {
+        eprintln!("best-selling: failed to set up schema: {}", err);
+        return;
+    }
+
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+
+        if let Err(err) = store.refresh(now).await {
+            eprintln!("best-selling: snapshot refresh failed: {}", err);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+const DEFAULT_ALPHA: f64 = 0.2;
+const DEFAULT_MAX_AGE_SECS: i64 = 24 * 60 * 60;
+
+struct EmaState {
+    ema: f64,
+    last_update: i64,
+}
+
+/// Tracks a per-series (product+platform) exponential moving average of
+/// price, restarting the series whenever it's gone stale for longer than
+/// `max_age_secs` rather than blending in an observation from a dead series.
+pub struct DealScorer {
+    alpha: f64,
+    max_age_secs: i64,
+    series: Mutex<HashMap<String, EmaState>>,
+}
+
+impl DealScorer {
+    pub fn new() -> Self {
+        Self::with_config(DEFAULT_ALPHA, DEFAULT_MAX_AGE_SECS)
+    }
+
+    pub fn with_config(alpha: f64, max_age_secs: i64) -> Self {
+        Self {
+            alpha,
+            max_age_secs,
+            series: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Updates the EMA for `key` with a new observation at `now` (unix
+    /// seconds) and returns the new EMA.
+    pub fn update(&self, key: &str, price: f64, now: i64) -> f64 {
+        let mut series = self.series.lock().unwrap();
+
+        let ema = match series.get(key) {
+            Some(state) if now - state.last_update <= self.max_age_secs => {
+                self.alpha * price + (1.0 - self.alpha) * state.ema
+            }
+            _ => price,
+        };
+
+        series.insert(key.to_string(), EmaState { ema, last_update: now });
+        ema
+    }
+}
+
+impl Default for DealScorer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns what percentage of `distribution` is at or below `value`.
+pub fn percentile_rank(value: f64, distribution: &[f64]) -> f64 {
+    if distribution.is_empty() {
+        return 50.0;
+    }
+
+    let below_or_equal = distribution.iter().filter(|&&p| p <= value).count();
+    (below_or_equal as f64 / distribution.len() as f64) * 100.0
+}
+
+/// Scores `price` (0-100, higher is a better deal) against the recent
+/// observed price distribution, blended with `ema` (the tracked trend price
+/// for this product/platform) so a price that's merely average for the
+/// window but below where the trend is heading still reads as a good deal.
+/// Produces a human-readable recommendation alongside the score.
+pub fn score(price: f64, distribution: &[f64], ema: f64) -> (u8, String) {
+    let percentile = percentile_rank(price, distribution);
+    let percentile_score = 100.0 - percentile;
+
+    let trend_score = if ema > 0.0 {
+        (((ema - price) / ema) * 100.0 + 50.0).clamp(0.0, 100.0)
+    } else {
+        50.0
+    };
+
+    let deal_score = ((percentile_score + trend_score) / 2.0).round().clamp(0.0, 100.0) as u8;
+
+    let bucket = if percentile <= 10.0 {
+        "bottom 10%"
+    } else if percentile <= 25.0 {
+        "bottom 25%"
+    } else if percentile <= 50.0 {
+        "bottom half"
+    } else if percentile <= 75.0 {
+        "top half"
+    } else {
+        "top 25%"
+    };
+
+    let trend_note = if ema > 0.0 && price < ema {
+        format!(", below its recent trend price of {:.2}", ema)
+    } else if ema > 0.0 && price > ema {
+        format!(", above its recent trend price of {:.2}", ema)
+    } else {
+        String::new()
+    };
+
+    let recommendation = format!(
+        "current price is in the {} of the last 30 days{}",
+        bucket, trend_note
+    );
+    (deal_score, recommendation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentile_rank_of_minimum_is_low() {
+        let dist = vec![10.0, 20.0, 30.0, 40.0, 50.0];
+        assert_eq!(percentile_rank(10.0, &dist), 20.0);
+    }
+
+    #[test]
+    fn ema_restarts_after_max_age() {
+        let scorer = DealScorer::with_config(0.2, 3600);
+        let first = scorer.update("k", 100.0, 0);
+        assert_eq!(first, 100.0);
+
+        let stale = scorer.update("k", 200.0, 10_000);
+        assert_eq!(stale, 200.0);
+    }
+
+    #[test]
+    fn ema_blends_within_window() {
+        let scorer = DealScorer::with_config(0.2, 3600);
+        scorer.update("k", 100.0, 0);
+        let blended = scorer.update("k", 200.0, 60);
+        assert!((blended - 120.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,38 @@
+/// Normalizes a GTIN-8, GTIN-12 (UPC-A) or GTIN-13 (EAN-13) barcode into the
+/// canonical 14-digit GTIN form used internally for cross-platform matching,
+/// so the same physical product lines up regardless of which length barcode
+/// a given vendor happened to publish.
+pub fn normalize(raw: &str) -> Option<String> {
+    let digits: String = raw.chars().filter(|c| c.is_ascii_digit()).collect();
+
+    match digits.len() {
+        8 | 12 | 13 | 14 => Some(format!("{:0>14}", digits)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pads_shorter_codes_to_fourteen_digits() {
+        assert_eq!(normalize("12345670"), Some("00000012345670".to_string()));
+        assert_eq!(normalize("036000291452"), Some("00036000291452".to_string()));
+        assert_eq!(normalize("4006381333931"), Some("04006381333931".to_string()));
+    }
+
+    #[test]
+    fn strips_non_digit_formatting() {
+        assert_eq!(
+            normalize("4-006381-333931"),
+            normalize("4006381333931")
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_lengths() {
+        assert_eq!(normalize("123"), None);
+        assert_eq!(normalize(""), None);
+    }
+}
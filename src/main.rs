@@ -2,6 +2,17 @@ use axum::{routing::get, Router, Json};
 use serde_json::{json, Value};
 use tower_http::cors::CorsLayer;
 
+mod best_selling;
+mod deal_scoring;
+mod gtin;
+mod optimization;
+mod price_comparison;
+mod price_history;
+mod raw_archive;
+mod result_cache;
+mod services;
+mod vendor_pool;
+
 #[tokio::main]
 async fn main() {
     let app = Router::new()
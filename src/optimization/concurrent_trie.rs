@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+#[derive(Default)]
+struct TrieNode {
+    children: HashMap<char, TrieNode>,
+    product_names: Vec<String>,
+}
+
+/// A thread-safe prefix trie over product names, used for fuzzy/prefix
+/// product lookups ahead of (or alongside) exact EAN matching.
+pub struct ConcurrentTrie {
+    root: RwLock<TrieNode>,
+}
+
+impl ConcurrentTrie {
+    pub fn new() -> Self {
+        Self { root: RwLock::new(TrieNode::default()) }
+    }
+
+    pub fn insert(&self, product_name: &str) {
+        let mut root = self.root.write().unwrap();
+        let mut node = &mut *root;
+        for ch in product_name.to_lowercase().chars() {
+            node = node.children.entry(ch).or_default();
+        }
+        node.product_names.push(product_name.to_string());
+    }
+
+    /// Returns every inserted product name reachable under `prefix`.
+    pub fn search_prefix(&self, prefix: &str) -> Vec<String> {
+        let root = self.root.read().unwrap();
+        let mut node = &*root;
+        for ch in prefix.to_lowercase().chars() {
+            match node.children.get(&ch) {
+                Some(next) => node = next,
+                None => return Vec::new(),
+            }
+        }
+
+        collect_names(node)
+    }
+}
+
+fn collect_names(node: &TrieNode) -> Vec<String> {
+    let mut names = node.product_names.clone();
+    for child in node.children.values() {
+        names.extend(collect_names(child));
+    }
+    names
+}
+
+impl Default for ConcurrentTrie {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_inserted_names_by_prefix() {
+        let trie = ConcurrentTrie::new();
+        trie.insert("iPhone 15");
+        trie.insert("iPhone 15 Pro");
+        trie.insert("Galaxy S24");
+
+        let mut matches = trie.search_prefix("iphone");
+        matches.sort();
+        assert_eq!(matches, vec!["iPhone 15".to_string(), "iPhone 15 Pro".to_string()]);
+    }
+
+    #[test]
+    fn unknown_prefix_returns_empty() {
+        let trie = ConcurrentTrie::new();
+        trie.insert("iPhone 15");
+        assert!(trie.search_prefix("galaxy").is_empty());
+    }
+}
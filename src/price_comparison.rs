@@ -1,19 +1,30 @@
 use axum::{
-    extract::Extension,
+    extract::{Extension, Query},
     http::StatusCode,
     response::Json,
-    routing::post,
+    routing::{get, post},
     Router,
 };
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use sqlx::PgPool;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use crate::optimization::{
-    bloom_filter::BloomFilter,
-    concurrent_trie::ConcurrentTrie,
-};
-use crate::services::deal_search::DealSearchService;
+use crate::optimization::concurrent_trie::ConcurrentTrie;
+use crate::best_selling::{self, BestSellingSnapshot, BestSellingStore};
+use crate::deal_scoring::{self, DealScorer};
+use crate::gtin;
+use crate::price_history::{self, PriceHistoryRow, PriceHistoryStore, RequestTime};
+use crate::raw_archive::{self, GenericJsonPriceParser, RawArchiveStore, ReplayDiff};
+use crate::result_cache::ResultCache;
+use crate::services::deal_search::{DealSearchService, SearchResult};
+
+const DEAL_SCORE_WINDOW_SECS: i64 = 30 * 24 * 60 * 60;
+const DEFAULT_CACHE_CAPACITY: usize = 1000;
+const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(300);
+const BEST_SELLING_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(60 * 60);
 
 #[derive(Debug, Deserialize)]
 pub struct PriceComparisonRequest {
@@ -22,6 +33,19 @@ pub struct PriceComparisonRequest {
     pub current_platform: String,
     pub product_category: Option<String>,
     pub user_id: Option<String>,
+    /// EAN/GTIN barcode, in whatever length the client has on hand (8/12/13/14
+    /// digits). Normalized before matching.
+    pub ean: Option<String>,
+}
+
+/// How an alternative was matched to the product being compared.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchConfidence {
+    /// Matched on a normalized, exact EAN/GTIN.
+    Ean,
+    /// Matched by name/trie fuzzy search.
+    Name,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -33,47 +57,190 @@ pub struct PlatformPrice {
     pub availability: bool,
     pub rating: Option<f64>,
     pub delivery_time: Option<String>,
+    pub ean: Option<String>,
+    pub match_confidence: MatchConfidence,
+    /// The parser version that produced this price, so a later parser
+    /// regression can be diagnosed against archived raw responses.
+    pub parser_version: i32,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Clone)]
 pub struct PriceComparisonResponse {
     pub current_platform: PlatformPrice,
     pub alternatives: Vec<PlatformPrice>,
     pub best_deal: PlatformPrice,
     pub potential_savings: f64,
     pub recommendations: Vec<String>,
+    /// 0-100, higher means the current best deal sits further into the
+    /// cheap end of its recent observed price distribution.
+    pub deal_score: u8,
+    pub deal_recommendation: String,
 }
 
 pub struct PriceComparisonService {
-    pool: PgPool,
     search_service: Arc<DealSearchService>,
-    bloom_filter: Arc<BloomFilter>,
     trie: Arc<ConcurrentTrie>,
+    history: Arc<PriceHistoryStore>,
+    deal_scorer: Arc<DealScorer>,
+    result_cache: Arc<ResultCache<PriceComparisonResponse>>,
+    best_selling: Arc<BestSellingStore>,
+    archive: Arc<RawArchiveStore>,
 }
 
 impl PriceComparisonService {
     pub fn new(pool: PgPool) -> Self {
+        Self::with_cache_config(pool, DEFAULT_CACHE_CAPACITY, DEFAULT_CACHE_TTL)
+    }
+
+    pub fn with_cache_config(pool: PgPool, cache_capacity: usize, cache_ttl: Duration) -> Self {
+        tokio::spawn(best_selling::run_snapshot_task(
+            pool.clone(),
+            BEST_SELLING_SNAPSHOT_INTERVAL,
+        ));
+
+        let history = Arc::new(PriceHistoryStore::new(pool.clone()));
+        let archive = Arc::new(RawArchiveStore::new(pool.clone()));
+        let search_service = Arc::new(DealSearchService::new(pool.clone()));
+
+        let ensure_history = history.clone();
+        tokio::spawn(async move {
+            if let Err(err) = ensure_history.ensure_schema().await {
+                eprintln!("price_history: failed to set up schema: {}", err);
+            }
+        });
+
+        let ensure_archive = archive.clone();
+        tokio::spawn(async move {
+            if let Err(err) = ensure_archive.ensure_schema().await {
+                eprintln!("raw_archive: failed to set up schema: {}", err);
+            }
+        });
+
+        let ensure_search = search_service.clone();
+        tokio::spawn(async move {
+            if let Err(err) = ensure_search.ensure_schema().await {
+                eprintln!("deal_search: failed to set up schema: {}", err);
+            }
+        });
+
         Self {
-            pool: pool.clone(),
-            search_service: Arc::new(DealSearchService::new(pool.clone())),
-            bloom_filter: Arc::new(BloomFilter::new(10000, 0.01)),
+            search_service,
             trie: Arc::new(ConcurrentTrie::new()),
+            history,
+            deal_scorer: Arc::new(DealScorer::new()),
+            result_cache: Arc::new(ResultCache::new(cache_capacity, cache_ttl)),
+            best_selling: Arc::new(BestSellingStore::new(pool.clone())),
+            archive,
         }
     }
 
-    pub async fn compare_prices(&self, req: PriceComparisonRequest) -> Result<PriceComparisonResponse, StatusCode> {
-        // Use bloom filter for quick duplicate detection
-        let _product_hash = format!("{}-{}", req.product_name, req.current_platform);
-        
-        // Search for the product across platforms using concurrent trie
-        let search_results = self.search_service
-            .search_product_across_platforms(&req.product_name)
+    /// Returns the latest best-selling snapshot for `category`, if the
+    /// background ranking task has produced one yet.
+    pub async fn best_selling(&self, category: &str) -> Result<Option<BestSellingSnapshot>, StatusCode> {
+        self.best_selling
+            .latest(category)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    /// Re-parses an archived raw vendor response with the current parser
+    /// and diffs it against the price that was stored when it was archived.
+    pub async fn replay(&self, record_id: &str) -> Result<Option<ReplayDiff>, StatusCode> {
+        self.archive
+            .replay(record_id, &GenericJsonPriceParser)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    /// Normalizes a request down to the cache/history key its alternatives
+    /// would be matched under, so identical lookups (by EAN if supplied,
+    /// otherwise by name) share a cache entry. The stored response embeds
+    /// request-relative fields (`current_platform`, `potential_savings`,
+    /// each alternative's `discount_percentage`), all derived from
+    /// `current_price`/`current_platform`, so those are folded into the key
+    /// too -- otherwise a second caller pricing the same product from a
+    /// different platform would be handed the first caller's numbers.
+    pub fn cache_key(req: &PriceComparisonRequest) -> String {
+        let product_key = match req.ean.as_deref().and_then(gtin::normalize) {
+            Some(ean) => format!("ean:{}", ean),
+            None => format!("name:{}", price_history::product_key(&req.product_name)),
+        };
+        format!("{}:{}:{}", product_key, req.current_platform, req.current_price)
+    }
+
+    pub fn cache_hits(&self) -> u64 {
+        self.result_cache.hits()
+    }
+
+    pub fn cache_misses(&self) -> u64 {
+        self.result_cache.misses()
+    }
+
+    /// Searches by exact name, then falls back to the prefix trie for
+    /// fuzzy matches (e.g. "iPhone 15" turning up "iPhone 15 Pro") when the
+    /// exact lookup comes up empty. Every searched name is recorded into the
+    /// trie so later, related lookups can find it as a prefix match.
+    async fn search_by_name(&self, product_name: &str) -> Result<Vec<SearchResult>, StatusCode> {
+        let exact = self
+            .search_service
+            .search_product_across_platforms(product_name)
             .await
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-        // Collect and sort results by price
-        let mut sorted_results = Vec::new();
-        
+        self.trie.insert(product_name);
+
+        if !exact.is_empty() {
+            return Ok(exact);
+        }
+
+        let mut fuzzy = Vec::new();
+        for candidate in self.trie.search_prefix(product_name) {
+            if candidate.eq_ignore_ascii_case(product_name) {
+                continue;
+            }
+
+            fuzzy.extend(
+                self.search_service
+                    .search_product_across_platforms(&candidate)
+                    .await
+                    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+            );
+        }
+
+        Ok(fuzzy)
+    }
+
+    pub async fn compare_prices(&self, req: PriceComparisonRequest) -> Result<PriceComparisonResponse, StatusCode> {
+        let cache_key = Self::cache_key(&req);
+        if let Some(cached) = self.result_cache.get(&cache_key) {
+            return Ok(cached);
+        }
+
+        let normalized_ean = req.ean.as_deref().and_then(gtin::normalize);
+
+        // Prefer an EAN-exact match over name/trie fuzzy matching when the
+        // caller supplied a barcode; fall back to name search if it comes up
+        // empty (e.g. a vendor hasn't indexed that EAN yet).
+        let (search_results, match_confidence) = if let Some(ean) = normalized_ean.as_deref() {
+            let ean_results = self.search_service
+                .search_by_ean(ean)
+                .await
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+            if ean_results.is_empty() {
+                (self.search_by_name(&req.product_name).await?, MatchConfidence::Name)
+            } else {
+                (ean_results, MatchConfidence::Ean)
+            }
+        } else {
+            (self.search_by_name(&req.product_name).await?, MatchConfidence::Name)
+        };
+
+        // Collect and sort results by price, keeping each one's raw vendor
+        // payload alongside it so the eventual best deal can be archived
+        // verbatim rather than re-serializing the parsed price.
+        let mut sorted_results: Vec<(PlatformPrice, Option<Vec<u8>>)> = Vec::new();
+
         // Convert search results to platform prices
         let mut alternatives = Vec::new();
         for result in search_results {
@@ -85,17 +252,20 @@ impl PriceComparisonService {
                 availability: result.in_stock,
                 rating: result.rating,
                 delivery_time: result.estimated_delivery,
+                ean: normalized_ean.clone(),
+                match_confidence,
+                parser_version: raw_archive::CURRENT_PARSER_VERSION,
             };
-            
-            sorted_results.push(platform_price.clone());
+
+            sorted_results.push((platform_price.clone(), result.raw_payload));
             alternatives.push(platform_price);
         }
 
         // Sort by price and get the best deal
-        sorted_results.sort_by(|a, b| a.price.partial_cmp(&b.price).unwrap_or(std::cmp::Ordering::Equal));
-        
-        let best_deal = sorted_results.into_iter().next()
-            .unwrap_or_else(|| PlatformPrice {
+        sorted_results.sort_by(|a, b| a.0.price.partial_cmp(&b.0.price).unwrap_or(std::cmp::Ordering::Equal));
+
+        let (best_deal, best_deal_raw_payload) = sorted_results.into_iter().next()
+            .unwrap_or_else(|| (PlatformPrice {
                 platform: req.current_platform.clone(),
                 price: req.current_price,
                 url: String::new(),
@@ -103,7 +273,10 @@ impl PriceComparisonService {
                 availability: true,
                 rating: None,
                 delivery_time: None,
-            });
+                ean: normalized_ean.clone(),
+                match_confidence: MatchConfidence::Name,
+                parser_version: raw_archive::CURRENT_PARSER_VERSION,
+            }, None));
 
         let current_platform = PlatformPrice {
             platform: req.current_platform.clone(),
@@ -113,6 +286,9 @@ impl PriceComparisonService {
             availability: true,
             rating: None,
             delivery_time: None,
+            ean: normalized_ean.clone(),
+            match_confidence: MatchConfidence::Name,
+            parser_version: raw_archive::CURRENT_PARSER_VERSION,
         };
 
         let potential_savings = req.current_price - best_deal.price;
@@ -133,28 +309,110 @@ impl PriceComparisonService {
             ));
         }
 
-        // Note: In production, we'd use a thread-safe mutable bloom filter
-        // For now, we'll skip caching in the bloom filter
+        let fetched_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        let key = price_history::product_key(&req.product_name);
+
+        // Read the distribution the new observation will be scored against
+        // before persisting it, so a product's first-ever comparison isn't
+        // scored against a one-entry distribution containing only itself.
+        let distribution = self
+            .history
+            .recent_prices(&key, &best_deal.platform, fetched_at - DEAL_SCORE_WINDOW_SECS)
+            .await
+            .unwrap_or_default();
 
-        Ok(PriceComparisonResponse {
+        let mut history_snapshot = alternatives.clone();
+        history_snapshot.push(current_platform.clone());
+        if let Err(err) = self
+            .history
+            .record(&key, req.product_category.as_deref(), &history_snapshot, fetched_at)
+            .await
+        {
+            eprintln!("failed to persist price history for {}: {}", key, err);
+        }
+
+        // Archive the winning vendor response so a future parser regression
+        // can be replayed and diffed against the raw bytes via /compare/replay.
+        // Only the ingestion pipeline has the true raw payload; when it
+        // hasn't captured one for this listing there's nothing honest to
+        // archive, so the record is skipped rather than re-serializing the
+        // already-parsed price (which would make every replay a no-op diff).
+        if let Some(raw_payload) = best_deal_raw_payload {
+            if let Err(err) = self
+                .archive
+                .archive(
+                    &best_deal.platform,
+                    &raw_payload,
+                    best_deal.price,
+                    best_deal.parser_version,
+                    fetched_at,
+                )
+                .await
+            {
+                eprintln!("failed to archive raw payload for {}: {}", key, err);
+            }
+        }
+
+        let ema_key = format!("{}:{}", key, best_deal.platform);
+        let ema = self.deal_scorer.update(&ema_key, best_deal.price, fetched_at);
+
+        let (deal_score, deal_recommendation) = deal_scoring::score(best_deal.price, &distribution, ema);
+
+        let response = PriceComparisonResponse {
             current_platform,
             alternatives,
             best_deal,
             potential_savings,
             recommendations,
-        })
+            deal_score,
+            deal_recommendation,
+        };
+
+        self.result_cache.insert(cache_key, response.clone());
+
+        Ok(response)
+    }
+
+    /// Looks up historical prices for `product_name`, resolved per-platform
+    /// according to `when`.
+    pub async fn price_history(
+        &self,
+        product_name: &str,
+        when: RequestTime,
+    ) -> Result<Vec<PriceHistoryRow>, StatusCode> {
+        let key = price_history::product_key(product_name);
+        self.history
+            .query(&key, when)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
     }
 }
 
 pub fn price_comparison_routes(pool: PgPool) -> Router {
     let service = Arc::new(PriceComparisonService::new(pool));
-    
+
     Router::new()
         .route("/compare", post(compare_prices))
         .route("/bulk-compare", post(bulk_compare))
+        .route("/compare/history", get(compare_history))
+        .route("/compare/best-selling", get(best_selling))
+        .route("/compare/replay", get(replay))
+        .route("/health", get(health))
         .layer(Extension(service))
 }
 
+async fn health(Extension(service): Extension<Arc<PriceComparisonService>>) -> Json<Value> {
+    Json(json!({
+        "status": "healthy",
+        "service": "price-comparison",
+        "cache_hits": service.cache_hits(),
+        "cache_misses": service.cache_misses(),
+    }))
+}
+
 async fn compare_prices(
     Extension(service): Extension<Arc<PriceComparisonService>>,
     Json(payload): Json<PriceComparisonRequest>,
@@ -163,6 +421,54 @@ async fn compare_prices(
     Ok(Json(response))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct HistoryQuery {
+    pub product: String,
+    pub as_of: Option<i64>,
+}
+
+async fn compare_history(
+    Extension(service): Extension<Arc<PriceComparisonService>>,
+    Query(params): Query<HistoryQuery>,
+) -> Result<Json<Vec<PriceHistoryRow>>, StatusCode> {
+    let when = match params.as_of {
+        Some(ts) => RequestTime::FirstAfter(ts),
+        None => RequestTime::Latest,
+    };
+
+    let rows = service.price_history(&params.product, when).await?;
+    Ok(Json(rows))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReplayQuery {
+    pub record_id: String,
+}
+
+/// Admin endpoint: re-parses an archived raw vendor response with the
+/// current parser and diffs it against the price stored when it was
+/// archived, to help diagnose a parser regression.
+async fn replay(
+    Extension(service): Extension<Arc<PriceComparisonService>>,
+    Query(params): Query<ReplayQuery>,
+) -> Result<Json<Option<ReplayDiff>>, StatusCode> {
+    let diff = service.replay(&params.record_id).await?;
+    Ok(Json(diff))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BestSellingQuery {
+    pub category: String,
+}
+
+async fn best_selling(
+    Extension(service): Extension<Arc<PriceComparisonService>>,
+    Query(params): Query<BestSellingQuery>,
+) -> Result<Json<Option<BestSellingSnapshot>>, StatusCode> {
+    let snapshot = service.best_selling(&params.category).await?;
+    Ok(Json(snapshot))
+}
+
 #[derive(Debug, Deserialize)]
 pub struct BulkComparisonRequest {
     pub products: Vec<PriceComparisonRequest>,
@@ -172,25 +478,39 @@ async fn bulk_compare(
     Extension(service): Extension<Arc<PriceComparisonService>>,
     Json(payload): Json<BulkComparisonRequest>,
 ) -> Result<Json<Vec<PriceComparisonResponse>>, StatusCode> {
-    let mut results = Vec::new();
-    
-    // Process in parallel for better performance
-    let futures: Vec<_> = payload.products
+    // Dedupe identical product requests within the batch so the same lookup
+    // isn't fired (and doesn't miss the cache) more than once.
+    let mut slot_by_key: HashMap<String, usize> = HashMap::new();
+    let mut unique_products = Vec::new();
+    let mut slot_for_position = Vec::with_capacity(payload.products.len());
+
+    for product in payload.products {
+        let key = PriceComparisonService::cache_key(&product);
+        let slot = *slot_by_key.entry(key).or_insert_with(|| {
+            unique_products.push(product);
+            unique_products.len() - 1
+        });
+        slot_for_position.push(slot);
+    }
+
+    // Compare each unique product once, awaiting the lookups one after another
+    let futures: Vec<_> = unique_products
         .into_iter()
         .map(|product| {
             let service = service.clone();
-            async move {
-                service.compare_prices(product).await
-            }
+            async move { service.compare_prices(product).await }
         })
         .collect();
-    
+
+    let mut unique_results = Vec::with_capacity(futures.len());
     for future in futures {
-        match future.await {
-            Ok(result) => results.push(result),
-            Err(_) => continue,
-        }
+        unique_results.push(future.await.ok());
     }
-    
+
+    let results = slot_for_position
+        .into_iter()
+        .filter_map(|slot| unique_results[slot].clone())
+        .collect();
+
     Ok(Json(results))
 }
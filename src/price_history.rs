@@ -0,0 +1,202 @@
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::price_comparison::PlatformPrice;
+
+/// How a price-history query should be anchored in time.
+#[derive(Debug, Clone, Copy)]
+pub enum RequestTime {
+    /// The most recently observed price per platform.
+    Latest,
+    /// The earliest observed price at or after `unix_ts`, falling back to the
+    /// latest price before it if nothing was observed afterwards.
+    FirstAfter(i64),
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct PriceHistoryRow {
+    pub platform: String,
+    pub price: f64,
+    pub in_stock: bool,
+    pub fetched_at: i64,
+    pub parser_version: i32,
+}
+
+/// Persists every observed `PlatformPrice` so comparisons can be replayed
+/// as-of an arbitrary point in time instead of only reflecting live data.
+pub struct PriceHistoryStore {
+    pool: PgPool,
+}
+
+impl PriceHistoryStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn ensure_schema(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS price_history (
+                id BIGSERIAL PRIMARY KEY,
+                product_key TEXT NOT NULL,
+                platform TEXT NOT NULL,
+                price DOUBLE PRECISION NOT NULL,
+                in_stock BOOLEAN NOT NULL,
+                fetched_at BIGINT NOT NULL
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS price_history_product_platform_idx \
+             ON price_history (product_key, platform, fetched_at)",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query("ALTER TABLE price_history ADD COLUMN IF NOT EXISTS product_category TEXT")
+            .execute(&self.pool)
+            .await?;
+
+        sqlx::query(
+            "ALTER TABLE price_history ADD COLUMN IF NOT EXISTS parser_version INTEGER NOT NULL DEFAULT 1",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS price_history_category_idx \
+             ON price_history (product_category, fetched_at)",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Records a snapshot of every platform price observed for `product_key`
+    /// at `fetched_at` (unix seconds). `product_category` is carried along so
+    /// the best-selling snapshot task can rank observations per category.
+    pub async fn record(
+        &self,
+        product_key: &str,
+        product_category: Option<&str>,
+        prices: &[PlatformPrice],
+        fetched_at: i64,
+    ) -> Result<(), sqlx::Error> {
+        for price in prices {
+            sqlx::query(
+                "INSERT INTO price_history (product_key, platform, price, in_stock, fetched_at, product_category, parser_version) \
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            )
+            .bind(product_key)
+            .bind(&price.platform)
+            .bind(price.price)
+            .bind(price.availability)
+            .bind(fetched_at)
+            .bind(product_category)
+            .bind(price.parser_version)
+            .execute(&self.pool)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns one row per platform, resolved according to `when`.
+    pub async fn query(
+        &self,
+        product_key: &str,
+        when: RequestTime,
+    ) -> Result<Vec<PriceHistoryRow>, sqlx::Error> {
+        let platforms: Vec<String> = sqlx::query_scalar(
+            "SELECT DISTINCT platform FROM price_history WHERE product_key = $1",
+        )
+        .bind(product_key)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut rows = Vec::with_capacity(platforms.len());
+        for platform in platforms {
+            if let Some(row) = self.resolve_platform(product_key, &platform, when).await? {
+                rows.push(row);
+            }
+        }
+
+        Ok(rows)
+    }
+
+    /// Returns every price observed for `product_key`/`platform` at or after
+    /// `since`, oldest first — the distribution deal scoring ranks against.
+    pub async fn recent_prices(
+        &self,
+        product_key: &str,
+        platform: &str,
+        since: i64,
+    ) -> Result<Vec<f64>, sqlx::Error> {
+        sqlx::query_scalar(
+            "SELECT price FROM price_history \
+             WHERE product_key = $1 AND platform = $2 AND fetched_at >= $3 \
+             ORDER BY fetched_at ASC",
+        )
+        .bind(product_key)
+        .bind(platform)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn resolve_platform(
+        &self,
+        product_key: &str,
+        platform: &str,
+        when: RequestTime,
+    ) -> Result<Option<PriceHistoryRow>, sqlx::Error> {
+        match when {
+            RequestTime::Latest => {
+                sqlx::query_as::<_, PriceHistoryRow>(
+                    "SELECT platform, price, in_stock, fetched_at, parser_version FROM price_history \
+                     WHERE product_key = $1 AND platform = $2 \
+                     ORDER BY fetched_at DESC LIMIT 1",
+                )
+                .bind(product_key)
+                .bind(platform)
+                .fetch_optional(&self.pool)
+                .await
+            }
+            RequestTime::FirstAfter(ts) => {
+                let first_after = sqlx::query_as::<_, PriceHistoryRow>(
+                    "SELECT platform, price, in_stock, fetched_at, parser_version FROM price_history \
+                     WHERE product_key = $1 AND platform = $2 AND fetched_at >= $3 \
+                     ORDER BY fetched_at ASC LIMIT 1",
+                )
+                .bind(product_key)
+                .bind(platform)
+                .bind(ts)
+                .fetch_optional(&self.pool)
+                .await?;
+
+                if first_after.is_some() {
+                    return Ok(first_after);
+                }
+
+                sqlx::query_as::<_, PriceHistoryRow>(
+                    "SELECT platform, price, in_stock, fetched_at, parser_version FROM price_history \
+                     WHERE product_key = $1 AND platform = $2 AND fetched_at < $3 \
+                     ORDER BY fetched_at DESC LIMIT 1",
+                )
+                .bind(product_key)
+                .bind(platform)
+                .bind(ts)
+                .fetch_optional(&self.pool)
+                .await
+            }
+        }
+    }
+}
+
+/// Normalizes a product name (trimmed, lowercased) into the key
+/// `price_history` is partitioned by.
+pub fn product_key(product_name: &str) -> String {
+    product_name.trim().to_lowercase()
+}
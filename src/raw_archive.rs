@@ -0,0 +1,189 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::io::{Read, Write};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use sqlx::PgPool;
+
+/// Bump whenever a vendor parser's extraction logic changes, so archived
+/// records can be replayed and diffed against what the old parser produced.
+pub const CURRENT_PARSER_VERSION: i32 = 1;
+
+/// Extracts a price from a raw vendor payload. `DealSearchService` does no
+/// parsing of its own (it reads listings the ingestion pipeline has already
+/// parsed); `GenericJsonPriceParser` is the parser `/compare/replay` runs
+/// archived records through.
+pub trait PriceParser {
+    fn version(&self) -> i32;
+    fn parse(&self, raw: &[u8]) -> Result<f64, String>;
+}
+
+#[derive(Default)]
+pub struct GenericJsonPriceParser;
+
+impl PriceParser for GenericJsonPriceParser {
+    fn version(&self) -> i32 {
+        CURRENT_PARSER_VERSION
+    }
+
+    fn parse(&self, raw: &[u8]) -> Result<f64, String> {
+        let value: serde_json::Value = serde_json::from_slice(raw).map_err(|e| e.to_string())?;
+        value
+            .get("price")
+            .and_then(|p| p.as_f64())
+            .ok_or_else(|| "payload has no numeric \"price\" field".to_string())
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ReplayDiff {
+    pub record_id: String,
+    pub archived_parser_version: i32,
+    pub current_parser_version: i32,
+    pub archived_price: f64,
+    pub reparsed_price: Option<f64>,
+    pub price_delta: Option<f64>,
+}
+
+/// A dated, content-addressed archive of raw vendor payloads, so a parser
+/// regression can be diagnosed by replaying the original bytes through a new
+/// `PriceParser` rather than guessing from the (already-parsed) price alone.
+pub struct RawArchiveStore {
+    pool: PgPool,
+}
+
+impl RawArchiveStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn ensure_schema(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS raw_archive (
+                record_id TEXT PRIMARY KEY,
+                platform TEXT NOT NULL,
+                fetched_at BIGINT NOT NULL,
+                parser_version INTEGER NOT NULL,
+                parsed_price DOUBLE PRECISION NOT NULL,
+                payload_gzip BYTEA NOT NULL
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Archives `raw` under a content-addressed id derived from its bytes;
+    /// re-archiving identical bytes is a no-op rather than a duplicate row.
+    pub async fn archive(
+        &self,
+        platform: &str,
+        raw: &[u8],
+        parsed_price: f64,
+        parser_version: i32,
+        fetched_at: i64,
+    ) -> Result<String, sqlx::Error> {
+        let record_id = content_hash(raw);
+        let payload_gzip = gzip(raw);
+
+        sqlx::query(
+            "INSERT INTO raw_archive (record_id, platform, fetched_at, parser_version, parsed_price, payload_gzip) \
+             VALUES ($1, $2, $3, $4, $5, $6) \
+             ON CONFLICT (record_id) DO NOTHING",
+        )
+        .bind(&record_id)
+        .bind(platform)
+        .bind(fetched_at)
+        .bind(parser_version)
+        .bind(parsed_price)
+        .bind(payload_gzip)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(record_id)
+    }
+
+    /// Re-parses the archived record with `parser` and diffs the result
+    /// against the price that was stored when it was first archived.
+    pub async fn replay(
+        &self,
+        record_id: &str,
+        parser: &dyn PriceParser,
+    ) -> Result<Option<ReplayDiff>, sqlx::Error> {
+        let row: Option<(i32, f64, Vec<u8>)> = sqlx::query_as(
+            "SELECT parser_version, parsed_price, payload_gzip FROM raw_archive WHERE record_id = $1",
+        )
+        .bind(record_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some((archived_parser_version, archived_price, payload_gzip)) = row else {
+            return Ok(None);
+        };
+
+        let reparsed_price = gunzip(&payload_gzip)
+            .ok()
+            .and_then(|raw| parser.parse(&raw).ok());
+        let price_delta = reparsed_price.map(|p| p - archived_price);
+
+        Ok(Some(ReplayDiff {
+            record_id: record_id.to_string(),
+            archived_parser_version,
+            current_parser_version: parser.version(),
+            archived_price,
+            reparsed_price,
+            price_delta,
+        }))
+    }
+}
+
+fn content_hash(raw: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    raw.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+fn gzip(raw: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(raw).expect("in-memory gzip write cannot fail");
+    encoder.finish().expect("in-memory gzip finish cannot fail")
+}
+
+fn gunzip(compressed: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut decoder = GzDecoder::new(compressed);
+    let mut raw = Vec::new();
+    decoder.read_to_end(&mut raw)?;
+    Ok(raw)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gzip_round_trips() {
+        let raw = b"{\"price\": 19.99}".to_vec();
+        assert_eq!(gunzip(&gzip(&raw)).unwrap(), raw);
+    }
+
+    #[test]
+    fn gunzip_of_corrupt_payload_errors_instead_of_panicking() {
+        assert!(gunzip(b"not a gzip payload").is_err());
+    }
+
+    #[test]
+    fn content_hash_is_stable_for_identical_bytes() {
+        assert_eq!(content_hash(b"abc"), content_hash(b"abc"));
+        assert_ne!(content_hash(b"abc"), content_hash(b"abd"));
+    }
+
+    #[test]
+    fn generic_parser_reads_price_field() {
+        let parser = GenericJsonPriceParser;
+        assert_eq!(parser.parse(b"{\"price\": 42.5}"), Ok(42.5));
+        assert!(parser.parse(b"{}").is_err());
+    }
+}
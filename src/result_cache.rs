@@ -0,0 +1,127 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+struct CacheEntry<V> {
+    value: V,
+    inserted_at: Instant,
+}
+
+/// A bounded, TTL'd LRU cache keyed by a normalized product key string, used so
+/// repeated or bulk comparisons don't re-hit `DealSearchService` for a
+/// product that was just looked up.
+pub struct ResultCache<V: Clone> {
+    capacity: usize,
+    ttl: Duration,
+    entries: Mutex<HashMap<String, CacheEntry<V>>>,
+    order: Mutex<VecDeque<String>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<V: Clone> ResultCache<V> {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+            order: Mutex::new(VecDeque::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<V> {
+        let mut entries = self.entries.lock().unwrap();
+
+        let fresh = entries
+            .get(key)
+            .map(|entry| entry.inserted_at.elapsed() <= self.ttl);
+
+        match fresh {
+            Some(true) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                self.touch(key);
+                entries.get(key).map(|entry| entry.value.clone())
+            }
+            Some(false) => {
+                entries.remove(key);
+                self.order.lock().unwrap().retain(|k| k != key);
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    pub fn insert(&self, key: String, value: V) {
+        let mut entries = self.entries.lock().unwrap();
+
+        if !entries.contains_key(&key) && entries.len() >= self.capacity {
+            let mut order = self.order.lock().unwrap();
+            if let Some(oldest) = order.pop_front() {
+                entries.remove(&oldest);
+            }
+        }
+
+        entries.insert(
+            key.clone(),
+            CacheEntry { value, inserted_at: Instant::now() },
+        );
+        self.touch(&key);
+    }
+
+    fn touch(&self, key: &str) {
+        let mut order = self.order.lock().unwrap();
+        order.retain(|k| k != key);
+        order.push_back(key.to_string());
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_least_recently_used_entry_over_capacity() {
+        let cache = ResultCache::new(2, Duration::from_secs(60));
+        cache.insert("a".into(), 1);
+        cache.insert("b".into(), 2);
+        cache.insert("c".into(), 3);
+
+        assert_eq!(cache.get("a"), None);
+        assert_eq!(cache.get("b"), Some(2));
+        assert_eq!(cache.get("c"), Some(3));
+    }
+
+    #[test]
+    fn expires_entries_past_ttl() {
+        let cache = ResultCache::new(10, Duration::from_millis(0));
+        cache.insert("a".into(), 1);
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(cache.get("a"), None);
+    }
+
+    #[test]
+    fn tracks_hit_and_miss_counts() {
+        let cache = ResultCache::new(10, Duration::from_secs(60));
+        cache.insert("a".into(), 1);
+        cache.get("a");
+        cache.get("missing");
+
+        assert_eq!(cache.hits(), 1);
+        assert_eq!(cache.misses(), 1);
+    }
+}
@@ -0,0 +1,133 @@
+use sqlx::PgPool;
+
+use crate::vendor_pool::{VendorError, VendorSourceConfig, VendorSourcePool};
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SearchResult {
+    pub platform: String,
+    pub price: f64,
+    pub url: String,
+    pub in_stock: bool,
+    pub rating: Option<f64>,
+    pub estimated_delivery: Option<String>,
+    /// The vendor's raw (pre-parse) response body, when the ingestion
+    /// pipeline kept a copy. Archived verbatim by `RawArchiveStore` so a
+    /// parser regression can be diagnosed by replaying these exact bytes.
+    pub raw_payload: Option<Vec<u8>>,
+}
+
+/// Looks up listings for a product across vendor platforms. Vendor data
+/// lives in `vendor_listings`, kept in sync by the ingestion pipeline
+/// upstream of this service. Fetches are routed through a `VendorSourcePool`
+/// so a single degraded vendor can't stall or fail the whole lookup.
+pub struct DealSearchService {
+    pool: PgPool,
+    vendor_pool: VendorSourcePool,
+}
+
+impl DealSearchService {
+    pub fn new(pool: PgPool) -> Self {
+        let mut vendor_pool = VendorSourcePool::new();
+        vendor_pool.register(VendorSourceConfig::new("amazon", 0));
+        vendor_pool.register(VendorSourceConfig::new("flipkart", 0));
+        vendor_pool.register(VendorSourceConfig::new("best_buy", 1));
+        vendor_pool.register(VendorSourceConfig::new("walmart", 1));
+
+        Self { pool, vendor_pool }
+    }
+
+    pub async fn ensure_schema(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS vendor_listings (
+                id BIGSERIAL PRIMARY KEY,
+                product_name TEXT NOT NULL,
+                ean TEXT,
+                platform TEXT NOT NULL,
+                price DOUBLE PRECISION NOT NULL,
+                url TEXT NOT NULL,
+                in_stock BOOLEAN NOT NULL DEFAULT TRUE,
+                rating DOUBLE PRECISION,
+                estimated_delivery TEXT
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query("ALTER TABLE vendor_listings ADD COLUMN IF NOT EXISTS raw_payload BYTEA")
+            .execute(&self.pool)
+            .await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS vendor_listings_ean_idx ON vendor_listings (ean)")
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn search_product_across_platforms(
+        &self,
+        product_name: &str,
+    ) -> Result<Vec<SearchResult>, sqlx::Error> {
+        let pool = self.pool.clone();
+        let name = product_name.to_string();
+
+        let results = self
+            .vendor_pool
+            .query_all(move |platform| {
+                let pool = pool.clone();
+                let name = name.clone();
+                async move { fetch_listing_by_name(&pool, &platform, &name).await }
+            })
+            .await;
+
+        Ok(results.into_iter().flatten().collect())
+    }
+
+    pub async fn search_by_ean(&self, ean: &str) -> Result<Vec<SearchResult>, sqlx::Error> {
+        let pool = self.pool.clone();
+        let ean = ean.to_string();
+
+        let results = self
+            .vendor_pool
+            .query_all(move |platform| {
+                let pool = pool.clone();
+                let ean = ean.clone();
+                async move { fetch_listing_by_ean(&pool, &platform, &ean).await }
+            })
+            .await;
+
+        Ok(results.into_iter().flatten().collect())
+    }
+}
+
+async fn fetch_listing_by_name(
+    pool: &PgPool,
+    platform: &str,
+    product_name: &str,
+) -> Result<Option<SearchResult>, VendorError> {
+    sqlx::query_as::<_, SearchResult>(
+        "SELECT platform, price, url, in_stock, rating, estimated_delivery, raw_payload \
+         FROM vendor_listings WHERE platform = $1 AND LOWER(product_name) = LOWER($2)",
+    )
+    .bind(platform)
+    .bind(product_name)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| VendorError::Failed(e.to_string()))
+}
+
+async fn fetch_listing_by_ean(
+    pool: &PgPool,
+    platform: &str,
+    ean: &str,
+) -> Result<Option<SearchResult>, VendorError> {
+    sqlx::query_as::<_, SearchResult>(
+        "SELECT platform, price, url, in_stock, rating, estimated_delivery, raw_payload \
+         FROM vendor_listings WHERE platform = $1 AND ean = $2",
+    )
+    .bind(platform)
+    .bind(ean)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| VendorError::Failed(e.to_string()))
+}
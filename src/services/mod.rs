@@ -0,0 +1 @@
+pub mod deal_search;
@@ -0,0 +1,233 @@
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use futures::stream::{FuturesUnordered, StreamExt};
+
+/// Why a vendor fetch didn't produce a result — distinct from the fetched
+/// value's own error type so the pool can decide whether to retry, fail over
+/// to the next tier, or mark the source unhealthy.
+#[derive(Debug, Clone)]
+pub enum VendorError {
+    Timeout,
+    Failed(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct VendorSourceConfig {
+    pub name: String,
+    /// Lower tiers are tried first; higher tiers are failover-only.
+    pub tier: u8,
+    pub rate_limit_per_sec: u32,
+    pub timeout: Duration,
+    pub max_retries: u32,
+    pub base_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Consecutive failures before the source is marked unhealthy.
+    pub failure_threshold: u32,
+    pub unhealthy_cooldown: Duration,
+}
+
+impl VendorSourceConfig {
+    pub fn new(name: impl Into<String>, tier: u8) -> Self {
+        Self {
+            name: name.into(),
+            tier,
+            rate_limit_per_sec: 5,
+            timeout: Duration::from_secs(60),
+            max_retries: 3,
+            base_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(10),
+            failure_threshold: 3,
+            unhealthy_cooldown: Duration::from_secs(120),
+        }
+    }
+}
+
+/// A simple token-bucket rate limiter, refilled continuously based on
+/// elapsed wall-clock time rather than a background ticker.
+struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate_per_sec: u32) -> Self {
+        let rate = rate_per_sec.max(1) as f64;
+        Self {
+            capacity: rate,
+            tokens: rate,
+            refill_per_sec: rate,
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn try_acquire(&mut self) -> bool {
+        let elapsed = self.last_refill.elapsed().as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = Instant::now();
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+struct VendorSourceState {
+    config: VendorSourceConfig,
+    limiter: Mutex<TokenBucket>,
+    consecutive_failures: Mutex<u32>,
+    unhealthy_since: Mutex<Option<Instant>>,
+}
+
+impl VendorSourceState {
+    fn is_eligible(&self) -> bool {
+        let unhealthy_since = self.unhealthy_since.lock().unwrap();
+        if let Some(since) = *unhealthy_since {
+            if since.elapsed() < self.config.unhealthy_cooldown {
+                return false;
+            }
+        }
+        drop(unhealthy_since);
+
+        self.limiter.lock().unwrap().try_acquire()
+    }
+
+    fn record_success(&self) {
+        *self.consecutive_failures.lock().unwrap() = 0;
+        *self.unhealthy_since.lock().unwrap() = None;
+    }
+
+    fn record_failure(&self) {
+        let mut failures = self.consecutive_failures.lock().unwrap();
+        *failures += 1;
+        if *failures >= self.config.failure_threshold {
+            *self.unhealthy_since.lock().unwrap() = Some(Instant::now());
+        }
+    }
+}
+
+/// A tiered, rate-limited, failover-aware pool of vendor sources, used by
+/// `DealSearchService::search_product_across_platforms` so a single
+/// degraded vendor API can't stall or fail an entire comparison.
+///
+/// Tier 0 sources are tried first; if none are healthy/within their rate
+/// limit or every tier-0 fetch fails, the pool falls through to the next
+/// tier. Within a tier, fetches run concurrently via `FuturesUnordered`.
+pub struct VendorSourcePool {
+    sources: Vec<VendorSourceState>,
+}
+
+impl VendorSourcePool {
+    pub fn new() -> Self {
+        Self { sources: Vec::new() }
+    }
+
+    pub fn register(&mut self, config: VendorSourceConfig) {
+        self.sources.push(VendorSourceState {
+            limiter: Mutex::new(TokenBucket::new(config.rate_limit_per_sec)),
+            consecutive_failures: Mutex::new(0),
+            unhealthy_since: Mutex::new(None),
+            config,
+        });
+    }
+
+    /// Runs `fetch` against every eligible source, tier by tier, returning
+    /// as soon as a tier produces at least one successful result.
+    pub async fn query_all<T, F, Fut>(&self, fetch: F) -> Vec<T>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<T, VendorError>>,
+    {
+        let mut tiers: Vec<u8> = self.sources.iter().map(|s| s.config.tier).collect();
+        tiers.sort_unstable();
+        tiers.dedup();
+
+        for tier in tiers {
+            let eligible: Vec<&VendorSourceState> = self
+                .sources
+                .iter()
+                .filter(|s| s.config.tier == tier && s.is_eligible())
+                .collect();
+
+            if eligible.is_empty() {
+                continue;
+            }
+
+            let mut in_flight = FuturesUnordered::new();
+            for source in eligible {
+                in_flight.push(Self::fetch_with_retry(source, &fetch));
+            }
+
+            let mut results = Vec::new();
+            while let Some(outcome) = in_flight.next().await {
+                if let Ok(value) = outcome {
+                    results.push(value);
+                }
+            }
+
+            if !results.is_empty() {
+                return results;
+            }
+        }
+
+        Vec::new()
+    }
+
+    async fn fetch_with_retry<T, F, Fut>(
+        source: &VendorSourceState,
+        fetch: &F,
+    ) -> Result<T, VendorError>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<T, VendorError>>,
+    {
+        let mut attempt = 0;
+        loop {
+            let call = fetch(source.config.name.clone());
+            let outcome = tokio::time::timeout(source.config.timeout, call)
+                .await
+                .unwrap_or(Err(VendorError::Timeout));
+
+            match outcome {
+                Ok(value) => {
+                    source.record_success();
+                    return Ok(value);
+                }
+                Err(err) => {
+                    source.record_failure();
+                    attempt += 1;
+                    if attempt > source.config.max_retries {
+                        return Err(err);
+                    }
+
+                    let backoff = source.config.base_backoff * 2u32.pow(attempt - 1);
+                    tokio::time::sleep(backoff.min(source.config.max_backoff)).await;
+                }
+            }
+        }
+    }
+}
+
+impl Default for VendorSourcePool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_bucket_depletes_and_refuses() {
+        let mut bucket = TokenBucket::new(1);
+        assert!(bucket.try_acquire());
+        assert!(!bucket.try_acquire());
+    }
+}